@@ -1,18 +1,21 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::env;
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Result};
 use clap::Args;
+use flate2::{write::GzEncoder, Compression};
 use fs_err as fs;
+use futures::stream::{self, StreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
 use resolve_path::PathResolveExt;
+use serde::{Deserialize, Serialize};
 
 use crate::asset_name::AssetName;
-use crate::auth_cookie::get_auth_cookie;
 use crate::data::Manifest;
 use crate::options::Global;
-use crate::roblox_api::{get_preferred_client, RobloxCredentials};
+use crate::roblox_api::get_preferred_client;
 
 #[derive(Debug, Args)]
 pub struct CreateCacheMapOptions {
@@ -25,15 +28,188 @@ pub struct CreateCacheMapOptions {
     /// A path to a file to contain the cache mapping.
     #[clap(long = "index-file")]
     pub index_file: PathBuf,
+
+    /// How many packed images to download at once.
+    #[clap(long, default_value_t = 8)]
+    pub concurrency: usize,
+
+    /// Store downloaded images at `cache_dir/<hash prefix>/<hash>` instead of
+    /// one file per asset ID, so that asset IDs whose packed images are
+    /// byte-identical share a single file on disk.
+    #[clap(long = "content-addressed")]
+    pub content_addressed: bool,
+
+    /// After building the index, delete any files in `cache_dir` that the
+    /// fresh index no longer references, reporting how many files and bytes
+    /// were reclaimed. Only files that look like cache entries (an asset ID
+    /// or a content hash) are ever considered for removal.
+    #[clap(long)]
+    pub prune: bool,
+
+    /// Also write a self-contained `.tar.gz` bundle containing every
+    /// referenced cache file plus a copy of the index with paths rewritten
+    /// to be relative to the bundle, suitable for shipping to another
+    /// machine with `unpack-cache-bundle`.
+    #[clap(long)]
+    pub bundle: Option<PathBuf>,
+}
+
+/// An entry in the cache index. Most entries are a downloaded, hashed packed
+/// image, but an asset that's only contributed to by a single input is just
+/// recorded by its source path, with no file of its own in `cache_dir`.
+///
+/// `Path` also doubles as the format of index entries written before this
+/// struct existed, so that an index from an older version of Tarmac doesn't
+/// fail to parse - those entries are simply treated as stale and re-fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum IndexEntry {
+    Cached(CachedImage),
+    Path(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CachedImage {
+    pub(crate) path: String,
+    pub(crate) hash: String,
+    pub(crate) len: u64,
+}
+
+/// Hash a downloaded image's bytes with a fast, non-cryptographic hash, for
+/// detecting whether a cached file is still intact - not for security.
+fn hash_contents(data: &[u8]) -> String {
+    format!("{:016x}", twox_hash::xxh3::hash64(data))
+}
+
+/// Where a downloaded image's content should live in `cache_dir`. In
+/// content-addressed mode, every asset ID whose image hashes the same lands
+/// on the same file, deduplicating identical packed spritesheets.
+fn cached_image_path(cache_dir: &Path, id: u64, hash: &str, content_addressed: bool) -> PathBuf {
+    if content_addressed {
+        cache_dir.join(&hash[0..2]).join(hash)
+    } else {
+        cache_dir.join(id.to_string())
+    }
+}
+
+/// Whether a file name in `cache_dir` looks like something this command
+/// created - either a plain asset ID or a content hash - rather than some
+/// unrelated file that happens to live in a directory the user pointed
+/// `--cache-dir` at. `--prune` never touches anything else.
+fn is_recognized_cache_file(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Recursively collect every file under `dir` that looks like a cache entry,
+/// along with its size in bytes, for `--prune` to consider removing.
+fn collect_cache_files(dir: &Path, files: &mut Vec<(PathBuf, u64)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        if !is_recognized_cache_file(name) {
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_cache_files(&path, files)?;
+        } else if file_type.is_file() {
+            files.push((path, entry.metadata()?.len()));
+        }
+    }
+
+    Ok(())
+}
+
+/// The name every cache file is stored under inside a bundle, so that an
+/// extracted bundle's layout doesn't depend on the `cache_dir` name it was
+/// originally built from.
+const BUNDLE_CACHE_DIR: &str = "cache";
+
+/// The name the rewritten index is stored under inside a bundle.
+const BUNDLE_INDEX_FILE: &str = "index.json";
+
+/// Where a cached file at `source` (inside `cache_dir`) is stored inside a
+/// bundle. Paths that aren't actually under `cache_dir` are joined onto the
+/// bundle's cache directory as they are, so a relative one is nested inside
+/// it and `unpack_cache_bundle` extracts it under its `cache_dir` like any
+/// other cache file.
+pub(crate) fn bundle_archive_path(cache_dir: &Path, source: &Path) -> PathBuf {
+    let relative = source.strip_prefix(cache_dir).unwrap_or(source);
+    Path::new(BUNDLE_CACHE_DIR).join(relative)
+}
+
+/// Write every referenced cache file, plus a copy of `index` with paths
+/// rewritten to be relative to the bundle, into a gzip-compressed tar at
+/// `bundle_path`.
+fn write_bundle(
+    bundle_path: &Path,
+    cache_dir: &Path,
+    index: &BTreeMap<u64, IndexEntry>,
+) -> Result<()> {
+    let file = fs::File::create(bundle_path)?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let mut bundled_index = BTreeMap::new();
+
+    for (&id, entry) in index {
+        match entry {
+            IndexEntry::Cached(cached) => {
+                let source = Path::new(&cached.path);
+                let archive_path = bundle_archive_path(cache_dir, source);
+
+                builder.append_path_with_name(source, &archive_path)?;
+
+                bundled_index.insert(
+                    id,
+                    IndexEntry::Cached(CachedImage {
+                        path: archive_path.display().to_string(),
+                        hash: cached.hash.clone(),
+                        len: cached.len,
+                    }),
+                );
+            }
+            IndexEntry::Path(_) => {
+                bundled_index.insert(id, entry.clone());
+            }
+        }
+    }
+
+    let index_bytes = serde_json::to_vec_pretty(&bundled_index)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(index_bytes.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, BUNDLE_INDEX_FILE, &index_bytes[..])?;
+
+    builder.into_inner()?.finish()?;
+
+    Ok(())
+}
+
+/// If `existing_index` already has a cache entry for `id` whose file is still
+/// present on disk and whose contents still match its recorded hash, reuse it
+/// instead of re-downloading the asset.
+fn reuse_cached_entry(existing_index: &BTreeMap<u64, IndexEntry>, id: u64) -> Option<IndexEntry> {
+    let Some(IndexEntry::Cached(entry)) = existing_index.get(&id) else {
+        return None;
+    };
+
+    let contents = fs::read(&entry.path).ok()?;
+    if contents.len() as u64 != entry.len || hash_contents(&contents) != entry.hash {
+        return None;
+    }
+
+    Some(IndexEntry::Cached(entry.clone()))
 }
 
 pub async fn create_cache_map(global: Global, options: CreateCacheMapOptions) -> Result<()> {
-    let api_client = get_preferred_client(RobloxCredentials {
-        token: global.auth.or_else(get_auth_cookie),
-        api_key: None,
-        user_id: None,
-        group_id: None,
-    })?;
+    let api_client = get_preferred_client(global.roblox_credentials(None, None, None))?;
 
     let project_path = match options.project_path {
         Some(path) => path,
@@ -60,22 +236,188 @@ pub async fn create_cache_map(global: Global, options: CreateCacheMapOptions) ->
         }
     }
 
-    let mut index: BTreeMap<u64, String> = BTreeMap::new();
+    let existing_index: BTreeMap<u64, IndexEntry> = fs::read(&options.index_file)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    let mut index: BTreeMap<u64, IndexEntry> = BTreeMap::new();
+    let mut to_download = Vec::new();
     for (id, contributing_assets) in uploaded_inputs {
         if contributing_assets.len() == 1 {
-            index.insert(id, contributing_assets[0].to_string());
+            index.insert(id, IndexEntry::Path(contributing_assets[0].to_string()));
+        } else if let Some(reused) = reuse_cached_entry(&existing_index, id) {
+            index.insert(id, reused);
         } else {
-            let contents = api_client.download_image(id).await?;
-            let path = options.cache_dir.join(id.to_string());
-            fs::write(&path, contents)?;
+            to_download.push(id);
+        }
+    }
+
+    let progress = ProgressBar::new(to_download.len() as u64);
+    progress.set_style(
+        ProgressStyle::with_template("{msg} [{bar:40.cyan/blue}] {pos}/{len}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    progress.set_message("Downloading packed images");
+
+    let downloaded: Vec<Result<(u64, CachedImage)>> = stream::iter(to_download)
+        .map(|id| {
+            let api_client = &api_client;
+            let cache_dir = &options.cache_dir;
+            let progress = &progress;
+            let content_addressed = options.content_addressed;
+
+            async move {
+                let contents = api_client.download_image(id).await?;
+                let hash = hash_contents(&contents);
+                let len = contents.len() as u64;
+
+                let path = cached_image_path(cache_dir, id, &hash, content_addressed);
+
+                // Write to a temp file first and rename into place, so a
+                // download that's interrupted partway through can never look
+                // like a complete, valid cache entry.
+                let temp_path = cache_dir.join(format!("{}.tmp", id));
+                fs::write(&temp_path, &contents)?;
+
+                if content_addressed && path.exists() {
+                    // Another asset ID already produced this exact content -
+                    // no need to keep a second copy around.
+                    fs::remove_file(&temp_path)?;
+                } else {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::rename(&temp_path, &path)?;
+                }
+
+                progress.inc(1);
+                Ok((
+                    id,
+                    CachedImage {
+                        path: path.display().to_string(),
+                        hash,
+                        len,
+                    },
+                ))
+            }
+        })
+        .buffer_unordered(options.concurrency)
+        .collect()
+        .await;
+
+    progress.finish_and_clear();
+
+    for result in downloaded {
+        let (id, entry) = result?;
+        index.insert(id, IndexEntry::Cached(entry));
+    }
+
+    if options.prune {
+        let referenced: HashSet<PathBuf> = index
+            .values()
+            .filter_map(|entry| match entry {
+                IndexEntry::Cached(cached) => Some(PathBuf::from(&cached.path)),
+                IndexEntry::Path(_) => None,
+            })
+            .collect();
+
+        let mut on_disk = Vec::new();
+        collect_cache_files(&options.cache_dir, &mut on_disk)?;
+
+        let mut reclaimed_files = 0u64;
+        let mut reclaimed_bytes = 0u64;
 
-            index.insert(id, path.display().to_string());
+        for (path, len) in on_disk {
+            if referenced.contains(&path) {
+                continue;
+            }
+
+            fs::remove_file(&path)?;
+            reclaimed_files += 1;
+            reclaimed_bytes += len;
         }
+
+        log::info!(
+            "Pruned {} unreferenced cache file(s), reclaiming {} bytes",
+            reclaimed_files,
+            reclaimed_bytes
+        );
     }
 
     let mut file = BufWriter::new(fs::File::create(&options.index_file)?);
     serde_json::to_writer_pretty(&mut file, &index)?;
     file.flush()?;
 
+    if let Some(bundle_path) = &options.bundle {
+        write_bundle(bundle_path, &options.cache_dir, &index)?;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn is_recognized_cache_file_accepts_hex_names() {
+        assert!(is_recognized_cache_file("1234567890"));
+        assert!(is_recognized_cache_file("deadbeef"));
+        assert!(is_recognized_cache_file("ABCDEF"));
+    }
+
+    #[test]
+    fn is_recognized_cache_file_rejects_unrelated_names() {
+        assert!(!is_recognized_cache_file(""));
+        assert!(!is_recognized_cache_file(".gitignore"));
+        assert!(!is_recognized_cache_file("readme.txt"));
+        assert!(!is_recognized_cache_file("123-456"));
+    }
+
+    #[test]
+    fn reuse_cached_entry_returns_none_when_missing() {
+        let index = BTreeMap::new();
+        assert!(reuse_cached_entry(&index, 1).is_none());
+    }
+
+    #[test]
+    fn reuse_cached_entry_returns_none_when_hash_mismatches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("1");
+        fs::write(&path, b"current contents").unwrap();
+
+        let mut index = BTreeMap::new();
+        index.insert(
+            1,
+            IndexEntry::Cached(CachedImage {
+                path: path.display().to_string(),
+                hash: "stale-hash".to_string(),
+                len: 16,
+            }),
+        );
+
+        assert!(reuse_cached_entry(&index, 1).is_none());
+    }
+
+    #[test]
+    fn reuse_cached_entry_reuses_matching_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("1");
+        let contents = b"current contents";
+        fs::write(&path, contents).unwrap();
+
+        let mut index = BTreeMap::new();
+        index.insert(
+            1,
+            IndexEntry::Cached(CachedImage {
+                path: path.display().to_string(),
+                hash: hash_contents(contents),
+                len: contents.len() as u64,
+            }),
+        );
+
+        assert!(reuse_cached_entry(&index, 1).is_some());
+    }
+}
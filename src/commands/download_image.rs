@@ -1,11 +1,7 @@
 use clap::Args;
 use fs_err as fs;
 
-use crate::{
-    auth_cookie::get_auth_cookie,
-    options::Global,
-    roblox_api::{get_preferred_client, RobloxCredentials},
-};
+use crate::{options::Global, roblox_api::get_preferred_client};
 
 #[derive(Debug, Args)]
 pub struct DownloadImageOptions {
@@ -21,12 +17,7 @@ pub async fn download_image(
     global: Global,
     options: DownloadImageOptions,
 ) -> anyhow::Result<()> {
-    let client = get_preferred_client(RobloxCredentials {
-        token: global.auth.or_else(get_auth_cookie),
-        api_key: None,
-        user_id: None,
-        group_id: None,
-    })?;
+    let client = get_preferred_client(global.roblox_credentials(None, None, None))?;
 
     let response = client.download_image(options.asset_id).await?;
     fs::write(options.output, response)?;
@@ -2,6 +2,7 @@ mod asset_list;
 mod create_cache_map;
 mod download_image;
 mod sync;
+mod unpack_cache_bundle;
 mod upload_image;
 
 pub use asset_list::*;
@@ -9,6 +10,7 @@ use clap::Subcommand;
 pub use create_cache_map::*;
 pub use download_image::*;
 pub use sync::*;
+pub use unpack_cache_bundle::*;
 pub use upload_image::*;
 
 #[derive(Debug, Subcommand)]
@@ -30,4 +32,8 @@ pub enum Command {
 
     /// Downloads a single image from the Roblox cloud.
     DownloadImage(DownloadImageOptions),
+
+    /// Extracts a cache bundle produced by `create-cache-map --bundle` into a
+    /// cache directory and index file.
+    UnpackCacheBundle(UnpackCacheBundleOptions),
 }
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::Args;
+use flate2::read::GzDecoder;
+use fs_err as fs;
+use tar::Archive;
+
+use crate::commands::create_cache_map::{CachedImage, IndexEntry};
+use crate::options::Global;
+
+/// The name every cache file is stored under inside a bundle, matching the
+/// layout `create-cache-map --bundle` writes.
+const BUNDLE_CACHE_DIR: &str = "cache";
+
+/// The name the rewritten index is stored under inside a bundle.
+const BUNDLE_INDEX_FILE: &str = "index.json";
+
+#[derive(Debug, Args)]
+pub struct UnpackCacheBundleOptions {
+    /// The bundle produced by `create-cache-map --bundle`.
+    pub bundle: PathBuf,
+
+    /// The directory to extract cached images into.
+    #[clap(long = "cache-dir")]
+    pub cache_dir: PathBuf,
+
+    /// Where to write the index file, rewritten to point at the
+    /// newly-extracted cache files.
+    #[clap(long = "index-file")]
+    pub index_file: PathBuf,
+}
+
+/// Rewrite a cache file's path from how `create-cache-map --bundle` stored it
+/// inside the bundle back to where it was just extracted to under
+/// `cache_dir`. A path that isn't under the bundle's cache directory is
+/// left untouched.
+fn rewrite_bundled_path(path: &str, cache_dir: &Path) -> String {
+    match Path::new(path).strip_prefix(BUNDLE_CACHE_DIR) {
+        Ok(relative) => cache_dir.join(relative).display().to_string(),
+        Err(_) => path.to_string(),
+    }
+}
+
+pub async fn unpack_cache_bundle(_: Global, options: UnpackCacheBundleOptions) -> Result<()> {
+    fs::create_dir_all(&options.cache_dir)?;
+
+    let archive_file = fs::File::open(&options.bundle)?;
+    let mut archive = Archive::new(GzDecoder::new(archive_file));
+
+    let mut index_bytes = None;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let archive_path = entry.path()?.into_owned();
+
+        if archive_path == Path::new(BUNDLE_INDEX_FILE) {
+            let mut buffer = Vec::new();
+            entry.read_to_end(&mut buffer)?;
+            index_bytes = Some(buffer);
+            continue;
+        }
+
+        let Ok(relative) = archive_path.strip_prefix(BUNDLE_CACHE_DIR) else {
+            continue;
+        };
+
+        let destination = options.cache_dir.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        entry.unpack(&destination)?;
+    }
+
+    let Some(index_bytes) = index_bytes else {
+        bail!("bundle is missing its {BUNDLE_INDEX_FILE}");
+    };
+
+    let bundled_index: BTreeMap<u64, IndexEntry> = serde_json::from_slice(&index_bytes)?;
+
+    let mut index = BTreeMap::new();
+    for (id, entry) in bundled_index {
+        let rewritten = match entry {
+            IndexEntry::Cached(CachedImage { path, hash, len }) => IndexEntry::Cached(CachedImage {
+                path: rewrite_bundled_path(&path, &options.cache_dir),
+                hash,
+                len,
+            }),
+            path_only => path_only,
+        };
+
+        index.insert(id, rewritten);
+    }
+
+    let mut file = BufWriter::new(fs::File::create(&options.index_file)?);
+    serde_json::to_writer_pretty(&mut file, &index)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::commands::create_cache_map::bundle_archive_path;
+
+    #[test]
+    fn bundle_and_unbundle_round_trip_a_cached_path() {
+        let cache_dir = Path::new("/home/user/project/.tarmac-cache");
+        let source = cache_dir.join("ab").join("abcdef1234567890");
+
+        let archive_path = bundle_archive_path(cache_dir, &source);
+        let restored = rewrite_bundled_path(&archive_path.display().to_string(), cache_dir);
+
+        assert_eq!(restored, source.display().to_string());
+    }
+
+    #[test]
+    fn rewrite_bundled_path_leaves_unrelated_paths_alone() {
+        let cache_dir = Path::new("/home/user/project/.tarmac-cache");
+
+        assert_eq!(
+            rewrite_bundled_path("some/other/path.png", cache_dir),
+            "some/other/path.png"
+        );
+    }
+}
@@ -9,9 +9,8 @@ use std::{borrow::Cow, path::PathBuf};
 
 use crate::{
     alpha_bleed::alpha_bleed,
-    auth_cookie::get_auth_cookie,
     options::Global,
-    roblox_api::{get_preferred_client, ImageUploadData, RobloxCredentials},
+    roblox_api::{get_preferred_client, AssetType, ImageUploadData},
 };
 
 #[derive(Debug, Args)]
@@ -47,8 +46,38 @@ pub struct UploadImageOptions {
     )]
     pub group_id: Option<u64>,
 
-    #[clap(long, value_parser(clap::builder::ValueParser::new(parse_resize_var)))]
+    /// Only has an effect on `decal` uploads; combining it with any other
+    /// asset type is an error.
+    #[clap(
+        long,
+        value_parser(clap::builder::ValueParser::new(parse_resize_var))
+    )]
     pub resize: Option<(u32, u32)>,
+
+    /// The kind of asset being uploaded. Only `decal` can be uploaded with a
+    /// `.ROBLOSECURITY` cookie; the other asset types require an Open Cloud
+    /// API key.
+    #[clap(long, value_enum, default_value_t = AssetTypeArg::Decal)]
+    pub asset_type: AssetTypeArg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AssetTypeArg {
+    Decal,
+    Audio,
+    Mesh,
+    Model,
+}
+
+impl From<AssetTypeArg> for AssetType {
+    fn from(value: AssetTypeArg) -> Self {
+        match value {
+            AssetTypeArg::Decal => AssetType::Decal,
+            AssetTypeArg::Audio => AssetType::Audio,
+            AssetTypeArg::Mesh => AssetType::Mesh,
+            AssetTypeArg::Model => AssetType::Model,
+        }
+    }
 }
 
 fn parse_resize_var(env: &str) -> anyhow::Result<(u32, u32)> {
@@ -63,40 +92,54 @@ fn parse_resize_var(env: &str) -> anyhow::Result<(u32, u32)> {
 }
 
 pub async fn upload_image(global: Global, options: UploadImageOptions) -> anyhow::Result<()> {
-    let image_data = fs::read(options.path)?;
-
-    let mut img = match options.resize {
-        Some((width, height)) => {
-            let img = image::load_from_memory(&image_data)?;
-            debug!(
-                "read image with dimensions {:?}, resizing to {:?}",
-                img.dimensions(),
-                (width, height)
-            );
-            let img = resize(&img, width, height, image::imageops::FilterType::Gaussian);
-            DynamicImage::ImageRgba8(img)
-        }
-        None => image::load_from_memory(&image_data)?,
-    };
+    let asset_type: AssetType = options.asset_type.into();
 
-    alpha_bleed(&mut img);
-
-    let (width, height) = img.dimensions();
+    if options.resize.is_some() && asset_type != AssetType::Decal {
+        bail!("--resize can only be used with --asset-type decal");
+    }
 
-    let mut encoded_image: Vec<u8> = Vec::new();
-    PngEncoder::new(&mut encoded_image).encode(&img.to_bytes(), width, height, img.color())?;
+    let raw_data = fs::read(&options.path)?;
+
+    // Only decals go through Tarmac's image pipeline (resizing and alpha
+    // bleeding); every other asset type is uploaded as-is.
+    let encoded_image = if asset_type == AssetType::Decal {
+        let mut img = match options.resize {
+            Some((width, height)) => {
+                let img = image::load_from_memory(&raw_data)?;
+                debug!(
+                    "read image with dimensions {:?}, resizing to {:?}",
+                    img.dimensions(),
+                    (width, height)
+                );
+                let img = resize(&img, width, height, image::imageops::FilterType::Gaussian);
+                DynamicImage::ImageRgba8(img)
+            }
+            None => image::load_from_memory(&raw_data)?,
+        };
+
+        alpha_bleed(&mut img);
+
+        let (width, height) = img.dimensions();
+
+        let mut encoded_image: Vec<u8> = Vec::new();
+        PngEncoder::new(&mut encoded_image).encode(&img.to_bytes(), width, height, img.color())?;
+        encoded_image
+    } else {
+        raw_data
+    };
 
-    let client = get_preferred_client(RobloxCredentials {
-        token: global.auth.or_else(get_auth_cookie),
-        api_key: global.api_key,
-        user_id: options.user_id,
-        group_id: options.group_id,
-    })?;
+    let api_key = global.api_key.clone();
+    let client = get_preferred_client(global.roblox_credentials(
+        api_key,
+        options.user_id,
+        options.group_id,
+    ))?;
 
     let upload_data = ImageUploadData {
-        image_data: Cow::Owned(encoded_image.to_vec()),
+        image_data: Cow::Owned(encoded_image),
         name: options.name,
         description: options.description,
+        asset_type,
     };
 
     let response = client.upload_image(upload_data).await?;
@@ -0,0 +1,12 @@
+//! A stable content hash used to detect whether an input's bytes have
+//! actually changed since the last time it was uploaded, so that `sync` can
+//! skip re-uploading (and re-moderating) assets that haven't changed.
+
+use sha2::{Digest, Sha256};
+
+/// Compute a stable, hex-encoded SHA-256 hash of an input's bytes.
+pub fn hash_bytes(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
@@ -3,11 +3,13 @@ mod asset_name;
 mod auth_cookie;
 mod codegen;
 mod commands;
+mod content_hash;
 mod data;
 mod dpi_scale;
 mod glob;
 mod lua_ast;
 mod options;
+mod retry;
 mod roblox_api;
 mod sync_backend;
 
@@ -31,12 +33,22 @@ async fn run(options: Options) -> Result<(), anyhow::Error> {
         }
         Command::Sync(_) => {
             // commands::sync(options.global, sync_options)?,
+            //
+            // TODO: once src/commands/sync.rs and src/data.rs land, wire sync
+            // up to use content_hash::hash_bytes for dedup (see
+            // [paradoxum-games/tarmac#chunk1-4]) and upload through the async
+            // LegacyClient/OpenCloudClient with bounded concurrency (see
+            // [paradoxum-games/tarmac#chunk1-5]) -- both requests only got as
+            // far as they could without those files.
             Err(anyhow!("unfinished"))
         }
         Command::CreateCacheMap(sub_options) => {
             commands::create_cache_map(options.global, sub_options).await
         }
         Command::AssetList(sub_options) => commands::asset_list(options.global, sub_options).await,
+        Command::UnpackCacheBundle(sub_options) => {
+            commands::unpack_cache_bundle(options.global, sub_options).await
+        }
     }?;
 
     Ok(())
@@ -1,7 +1,14 @@
-use crate::commands::Command;
+use std::time::Duration;
+
 use clap::Parser;
 use secrecy::SecretString;
 
+use crate::{
+    auth_cookie::get_auth_cookie,
+    commands::Command,
+    roblox_api::{OAuth2Credentials, RobloxCredentials},
+};
+
 #[derive(Debug, Parser)]
 #[clap(about = env!("CARGO_PKG_DESCRIPTION"))]
 pub struct Options {
@@ -32,8 +39,82 @@ pub struct Global {
     )]
     pub api_key: Option<SecretString>,
 
+    /// The client ID of an OAuth2 application to authenticate as, using the
+    /// client-credentials grant. Must be paired with --oauth2-client-secret.
+    /// This is the recommended way to authenticate service accounts and CI
+    /// jobs, which can't ship a personal .ROBLOSECURITY cookie.
+    #[clap(
+        long,
+        global(true),
+        env("TARMAC_OAUTH2_CLIENT_ID"),
+        conflicts_with("auth"),
+        conflicts_with("api_key"),
+        requires("oauth2_client_secret")
+    )]
+    pub oauth2_client_id: Option<String>,
+
+    /// The client secret of an OAuth2 application to authenticate as. Must
+    /// be paired with --oauth2-client-id.
+    #[clap(
+        long,
+        global(true),
+        env("TARMAC_OAUTH2_CLIENT_SECRET"),
+        hide_env_values(true),
+        conflicts_with("auth"),
+        conflicts_with("api_key"),
+        requires("oauth2_client_id")
+    )]
+    pub oauth2_client_secret: Option<SecretString>,
+
     /// Sets verbosity level. Can be specified multiple times to increase the verbosity
     /// of this program.
     #[clap(long = "verbose", short, global(true), action(clap::ArgAction::Count))]
     pub verbosity: u8,
+
+    /// The maximum number of attempts to make for a single request before
+    /// giving up and returning the most recent error. Requests are retried
+    /// when Roblox responds with 429 Too Many Requests or a 5xx status.
+    #[clap(long, global(true), default_value_t = 5)]
+    pub max_retries: u32,
+
+    /// How long, in seconds, to wait for a connection to a Roblox API to be
+    /// established before giving up.
+    #[clap(long, global(true), default_value_t = 30)]
+    pub connect_timeout_secs: u64,
+
+    /// How long, in seconds, to wait for a Roblox API request to complete
+    /// before giving up.
+    #[clap(long, global(true), default_value_t = 120)]
+    pub request_timeout_secs: u64,
+}
+
+impl Global {
+    /// Build [`RobloxCredentials`] from these global flags, pairing them with
+    /// the per-command `api_key`, `user_id`, and `group_id` (commands that
+    /// don't accept an API key or a creator ID should just pass `None`).
+    pub fn roblox_credentials(
+        self,
+        api_key: Option<SecretString>,
+        user_id: Option<u64>,
+        group_id: Option<u64>,
+    ) -> RobloxCredentials {
+        let oauth2 = match (self.oauth2_client_id, self.oauth2_client_secret) {
+            (Some(client_id), Some(client_secret)) => Some(OAuth2Credentials {
+                client_id,
+                client_secret,
+            }),
+            _ => None,
+        };
+
+        RobloxCredentials {
+            token: self.auth.or_else(get_auth_cookie),
+            api_key,
+            oauth2,
+            user_id,
+            group_id,
+            max_retries: self.max_retries,
+            connect_timeout: Duration::from_secs(self.connect_timeout_secs),
+            request_timeout: Duration::from_secs(self.request_timeout_secs),
+        }
+    }
 }
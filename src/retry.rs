@@ -0,0 +1,90 @@
+//! Shared helpers for retrying requests against Roblox's APIs when they fail
+//! for transient reasons (rate limiting or a server-side error), used by the
+//! async Roblox API client implementations.
+
+use std::time::{Duration, SystemTime};
+
+use rand::Rng;
+use reqwest::{header::HeaderMap, StatusCode};
+
+/// Starting delay for the first backoff retry of a transient server error.
+const BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound on how long any single backoff sleep is allowed to be.
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Whether an HTTP status is worth retrying: Roblox rate-limiting us, or a
+/// transient failure on their end.
+pub fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// How long to sleep before the next attempt, given how many attempts have
+/// already been made. Doubles for each prior attempt, caps at `MAX_DELAY`,
+/// and adds up to 50% jitter so that many clients backing off at once don't
+/// all retry in lockstep.
+pub fn backoff_delay(attempt: u32) -> Duration {
+    let exponential = BASE_DELAY.saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX));
+    let capped = exponential.min(MAX_DELAY);
+    let jitter = rand::thread_rng().gen_range(0.0..=0.5);
+
+    capped.mul_f64(1.0 + jitter).min(MAX_DELAY)
+}
+
+/// Parse a `Retry-After` header value, which may be either a number of
+/// seconds or an HTTP-date, per RFC 7231.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let target = httpdate::parse_http_date(value).ok()?;
+    target.duration_since(SystemTime::now()).ok()
+}
+
+/// Pull a `Retry-After` delay out of a response's headers, if present.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_retry_after_seconds() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("  5  "), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn parse_retry_after_http_date() {
+        let future = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+        let delay = parse_retry_after(&future).expect("should parse an HTTP-date");
+
+        // Allow some slack for the time elapsed between formatting `future`
+        // and parsing it back out above.
+        assert!(delay <= Duration::from_secs(60));
+        assert!(delay > Duration::from_secs(55));
+    }
+
+    #[test]
+    fn parse_retry_after_garbage() {
+        assert_eq!(parse_retry_after("not a valid value"), None);
+    }
+
+    #[test]
+    fn backoff_delay_increases_and_caps() {
+        let first = backoff_delay(0);
+        let last = backoff_delay(20);
+
+        assert!(first >= BASE_DELAY);
+        assert!(first <= BASE_DELAY.mul_f64(1.5));
+        assert!(last <= MAX_DELAY);
+    }
+}
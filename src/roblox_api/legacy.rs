@@ -2,13 +2,14 @@ use std::{
     fmt::{self, Write},
     marker::PhantomData,
     str::FromStr,
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Result};
 use async_trait::async_trait;
 use log::info;
 use reqwest::{
-    header::{HeaderValue, COOKIE},
+    header::{HeaderValue, AUTHORIZATION, COOKIE},
     Client, Request, Response, StatusCode,
 };
 use secrecy::ExposeSecret;
@@ -16,12 +17,36 @@ use serde::Deserialize;
 use tokio::sync::RwLock;
 
 use crate::auth_cookie::get_csrf_token;
+use crate::retry::{backoff_delay, is_transient, retry_after};
 use xml::{
     name::OwnedName,
     reader::{EventReader, XmlEvent},
 };
 
-use super::{ImageUploadData, RobloxApiClient, RobloxApiError, RobloxCredentials, UploadResponse};
+use super::{
+    ImageUploadData, OAuth2Credentials, RobloxApiClient, RobloxApiError, RobloxCredentials,
+    UploadResponse,
+};
+
+/// The endpoint Tarmac exchanges OAuth2 client-credentials for a short-lived
+/// access token at.
+const OAUTH2_TOKEN_ENDPOINT: &str = "https://apis.roblox.com/oauth/v1/token";
+
+/// How much earlier than its stated expiry to treat a cached access token as
+/// expired, so a request doesn't race a token that's about to lapse.
+const OAUTH2_EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// A cached OAuth2 access token and when it should be considered expired.
+struct OAuth2Token {
+    access_token: String,
+    expires_at: Instant,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuth2TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
 
 /// Internal representation of what the asset upload endpoint returns, before
 /// we've handled any errors.
@@ -37,6 +62,7 @@ struct RawUploadResponse {
 pub struct LegacyClient<'a> {
     credentials: RobloxCredentials,
     csrf_token: RwLock<Option<HeaderValue>>,
+    oauth2_token: RwLock<Option<OAuth2Token>>,
     client: Client,
     _marker: PhantomData<&'a ()>,
 }
@@ -50,6 +76,13 @@ impl<'a> fmt::Debug for LegacyClient<'a> {
 #[async_trait]
 impl<'a> RobloxApiClient<'a> for LegacyClient<'a> {
     fn new(credentials: RobloxCredentials) -> Result<Self> {
+        let client = Client::builder()
+            .connect_timeout(credentials.connect_timeout)
+            .timeout(credentials.request_timeout)
+            .gzip(true)
+            .brotli(true)
+            .build()?;
+
         match &credentials.token {
             Some(token) => {
                 let csrf_token = match get_csrf_token(token) {
@@ -63,14 +96,16 @@ impl<'a> RobloxApiClient<'a> for LegacyClient<'a> {
                 Ok(Self {
                     credentials,
                     csrf_token,
-                    client: Client::new(),
+                    oauth2_token: RwLock::new(None),
+                    client,
                     _marker: PhantomData::default(),
                 })
             }
             _ => Ok(Self {
                 credentials,
                 csrf_token: RwLock::new(None),
-                client: Client::new(),
+                oauth2_token: RwLock::new(None),
+                client,
                 _marker: PhantomData::default(),
             }),
         }
@@ -80,7 +115,7 @@ impl<'a> RobloxApiClient<'a> for LegacyClient<'a> {
         let url = format!("https://assetdelivery.roblox.com/v1/asset/?id={}", id);
 
         let mut response = self
-            .execute_with_csrf_retry(|client| Ok(client.get(&url).build()?))
+            .execute_with_retries(|client| Ok(client.get(&url).build()?))
             .await?;
 
         let mut buffer = Vec::new();
@@ -132,7 +167,7 @@ impl<'a> RobloxApiClient<'a> for LegacyClient<'a> {
             let url = format!("https://assetdelivery.roblox.com/v1/asset/?id={}", asset_id);
 
             let mut response = self
-                .execute_with_csrf_retry(|client| Ok(client.get(&url).build()?))
+                .execute_with_retries(|client| Ok(client.get(&url).build()?))
                 .await?;
 
             let mut buffer = Vec::new();
@@ -146,6 +181,12 @@ impl<'a> RobloxApiClient<'a> for LegacyClient<'a> {
 
     /// Upload an image, returning an error if anything goes wrong.
     async fn upload_image(&self, data: ImageUploadData<'a>) -> Result<UploadResponse> {
+        if data.asset_type.requires_open_cloud() {
+            bail!(RobloxApiError::OpenCloudRequired {
+                asset_type: data.asset_type
+            });
+        }
+
         let response = self.upload_image_raw(data).await?;
 
         // Some other errors will be reported inside the response, even
@@ -177,7 +218,7 @@ impl<'a> LegacyClient<'a> {
         }
 
         let mut response = self
-            .execute_with_csrf_retry(|client| {
+            .execute_with_retries(|client| {
                 Ok(client
                     .post(&url)
                     .query(&[
@@ -213,9 +254,13 @@ impl<'a> LegacyClient<'a> {
         F: Fn(&Client) -> Result<Request>,
     {
         let mut request = make_request(&self.client)?;
-        self.attach_headers(&mut request).await;
+        self.attach_headers(&mut request).await?;
 
-        let response = self.client.execute(request)?;
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .map_err(into_timeout_error)?;
 
         match response.status() {
             StatusCode::FORBIDDEN => {
@@ -226,9 +271,13 @@ impl<'a> LegacyClient<'a> {
                     *csrf_token = Some(csrf.clone());
 
                     let mut new_request = make_request(&self.client)?;
-                    self.attach_headers(&mut new_request).await;
+                    self.attach_headers(&mut new_request).await?;
 
-                    Ok(self.client.execute(new_request)?)
+                    Ok(self
+                        .client
+                        .execute(new_request)
+                        .await
+                        .map_err(into_timeout_error)?)
                 } else {
                     // If the response did not return a CSRF token for us to
                     // retry with, this request was likely forbidden for other
@@ -241,10 +290,47 @@ impl<'a> LegacyClient<'a> {
         }
     }
 
+    /// Execute a request generated by the given function, retrying on top of
+    /// [`Self::execute_with_csrf_retry`] when Roblox rate-limits us or
+    /// returns a transient server error. Rate-limit retries sleep for
+    /// whatever the `Retry-After` header specifies; server-error retries use
+    /// exponential backoff with jitter. Exhausting `max_retries` attempts
+    /// returns the last response received, whatever its status.
+    async fn execute_with_retries<F>(&self, make_request: F) -> Result<Response>
+    where
+        F: Fn(&Client) -> Result<Request>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            let response = self.execute_with_csrf_retry(&make_request).await?;
+            let status = response.status();
+
+            if !is_transient(status) || attempt + 1 >= self.credentials.max_retries {
+                return Ok(response);
+            }
+
+            let delay = retry_after(response.headers()).unwrap_or_else(|| backoff_delay(attempt));
+
+            log::warn!(
+                "Roblox API returned {} on attempt {}, retrying in {:?}...",
+                status,
+                attempt + 1,
+                delay
+            );
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
     /// Attach required headers to a request object before sending it to a
     /// Roblox API, like authentication and CSRF protection.
-    async fn attach_headers(&self, request: &mut Request) {
-        if let Some(auth_token) = &self.credentials.token {
+    async fn attach_headers(&self, request: &mut Request) -> Result<()> {
+        if let Some(oauth2) = &self.credentials.oauth2 {
+            let bearer = self.oauth2_bearer_token(oauth2).await?;
+            request.headers_mut().insert(AUTHORIZATION, bearer);
+        } else if let Some(auth_token) = &self.credentials.token {
             let cookie_value = format!(".ROBLOSECURITY={}", auth_token.expose_secret());
 
             request.headers_mut().insert(
@@ -258,5 +344,75 @@ impl<'a> LegacyClient<'a> {
         if let Some(csrf) = csrf_token.clone() {
             request.headers_mut().insert("X-CSRF-Token", csrf);
         }
+
+        Ok(())
+    }
+
+    /// Return a cached, still-valid OAuth2 access token as a `Bearer` header
+    /// value, refreshing it via the client-credentials grant first if it's
+    /// missing or expired.
+    async fn oauth2_bearer_token(&self, oauth2: &OAuth2Credentials) -> Result<HeaderValue> {
+        {
+            let cached = self.oauth2_token.read().await;
+            if let Some(token) = cached.as_ref() {
+                if token.expires_at > Instant::now() {
+                    return bearer_header(&token.access_token);
+                }
+            }
+        }
+
+        let mut cached = self.oauth2_token.write().await;
+
+        // Someone else may have refreshed the token while we were waiting on
+        // the write lock.
+        if let Some(token) = cached.as_ref() {
+            if token.expires_at > Instant::now() {
+                return bearer_header(&token.access_token);
+            }
+        }
+
+        let response: OAuth2TokenResponse = self
+            .client
+            .post(OAUTH2_TOKEN_ENDPOINT)
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", oauth2.client_id.as_str()),
+                ("client_secret", oauth2.client_secret.expose_secret()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let expires_in =
+            Duration::from_secs(response.expires_in).saturating_sub(OAUTH2_EXPIRY_MARGIN);
+
+        let header = bearer_header(&response.access_token)?;
+
+        *cached = Some(OAuth2Token {
+            access_token: response.access_token,
+            expires_at: Instant::now() + expires_in,
+        });
+
+        Ok(header)
+    }
+}
+
+fn bearer_header(access_token: &str) -> Result<HeaderValue> {
+    let mut value = HeaderValue::from_str(&format!("Bearer {}", access_token))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Map a `reqwest::Error` that came back from connecting or timing out into
+/// [`RobloxApiError::Timeout`], so that callers (and the retry loop) can tell
+/// a timeout apart from other HTTP failures. Any other error passes through
+/// unchanged.
+fn into_timeout_error(err: reqwest::Error) -> anyhow::Error {
+    if err.is_timeout() {
+        RobloxApiError::Timeout.into()
+    } else {
+        err.into()
     }
 }
@@ -1,6 +1,8 @@
+mod legacy;
 mod open_cloud;
 
 use std::borrow::Cow;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use anyhow::{bail, Result};
@@ -10,13 +12,50 @@ use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use self::legacy::LegacyClient;
 use self::open_cloud::OpenCloudClient;
 
+/// Credentials for an OAuth2 client-credentials grant, used to authenticate
+/// as a service account rather than a logged-in user. Tarmac exchanges these
+/// for a short-lived access token and refreshes it as needed.
+#[derive(Clone, Debug)]
+pub struct OAuth2Credentials {
+    pub client_id: String,
+    pub client_secret: SecretString,
+}
+
+/// The kind of asset being uploaded. Tarmac originally only ever uploaded
+/// Decal images, but the Open Cloud assets API accepts several other asset
+/// types, so long as the creator is reachable through an API key rather than
+/// a `.ROBLOSECURITY` cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetType {
+    Decal,
+    Audio,
+    Mesh,
+    Model,
+}
+
+impl AssetType {
+    /// Only `Decal` assets can be uploaded through the legacy
+    /// `data.roblox.com` endpoint; every other type requires Open Cloud.
+    pub fn requires_open_cloud(self) -> bool {
+        !matches!(self, AssetType::Decal)
+    }
+}
+
+impl Default for AssetType {
+    fn default() -> Self {
+        AssetType::Decal
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ImageUploadData<'a> {
     pub image_data: Cow<'a, [u8]>,
     pub name: String,
     pub description: String,
+    pub asset_type: AssetType,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,8 +69,19 @@ pub struct UploadResponse {
 pub struct RobloxCredentials {
     pub token: Option<SecretString>,
     pub api_key: Option<SecretString>,
+    pub oauth2: Option<OAuth2Credentials>,
     pub user_id: Option<u64>,
     pub group_id: Option<u64>,
+
+    /// The maximum number of attempts to make for a single request before
+    /// giving up.
+    pub max_retries: u32,
+
+    /// How long to wait for a connection to a Roblox API to be established.
+    pub connect_timeout: Duration,
+
+    /// How long to wait for a Roblox API request to complete.
+    pub request_timeout: Duration,
 }
 
 #[async_trait]
@@ -51,7 +101,7 @@ pub trait RobloxApiClient<'a> {
         data: ImageUploadData<'a>,
     ) -> Result<UploadResponse>;
 
-    fn download_image(&self, id: u64) -> Result<Vec<u8>>;
+    async fn download_image(&self, id: u64) -> Result<Vec<u8>>;
 }
 
 #[derive(Debug, Error)]
@@ -100,6 +150,15 @@ pub enum RobloxApiError {
 
     #[error("Failed to parse asset ID from asset get response")]
     MalformedAssetId(#[from] std::num::ParseIntError),
+
+    #[error("{asset_type:?} assets can only be uploaded with an Open Cloud API key, not a .ROBLOSECURITY cookie")]
+    OpenCloudRequired { asset_type: AssetType },
+
+    #[error("{asset_type:?} assets are not yet supported by the Open Cloud assets API")]
+    UnsupportedAssetType { asset_type: AssetType },
+
+    #[error("Request to Roblox API timed out")]
+    Timeout,
 }
 
 pub fn get_preferred_client<'a>(
@@ -109,6 +168,7 @@ pub fn get_preferred_client<'a>(
         RobloxCredentials {
             token: None,
             api_key: None,
+            oauth2: None,
             ..
         } => bail!(RobloxApiError::MissingAuth),
 
@@ -122,6 +182,10 @@ pub fn get_preferred_client<'a>(
             api_key: Some(_), ..
         } => Ok(Box::new(OpenCloudClient::new(credentials)?)),
 
+        RobloxCredentials {
+            oauth2: Some(_), ..
+        } => Ok(Box::new(LegacyClient::new(credentials)?)),
+
         RobloxCredentials {
             token: Some(_),
             user_id,
@@ -135,8 +199,7 @@ Tarmac will attempt to upload to the user currently logged into Roblox Studio, o
 If you mean to use the Open Cloud API, make sure to provide an API key!")
             };
 
-            todo!();
-            // Ok(Box::new(LegacyClient::new(credentials)?))
+            Ok(Box::new(LegacyClient::new(credentials)?))
         }
     }
 }
@@ -5,8 +5,8 @@ use std::time::Duration;
 
 use rbxcloud::rbx::{
     assets::{
-        AssetCreation, AssetCreationContext, AssetCreator, AssetGroupCreator, AssetType,
-        AssetUserCreator,
+        AssetCreation, AssetCreationContext, AssetCreator, AssetGroupCreator,
+        AssetType as RbxAssetType, AssetUserCreator,
     },
     error::Error as RbxCloudError,
     CreateAssetWithContents, GetAsset, RbxAssets, RbxCloud,
@@ -14,7 +14,24 @@ use rbxcloud::rbx::{
 use reqwest::StatusCode;
 use secrecy::ExposeSecret;
 
-use super::{ImageUploadData, RobloxApiClient, RobloxApiError, RobloxCredentials, UploadResponse};
+use crate::retry::{backoff_delay, is_transient};
+use super::{
+    AssetType, ImageUploadData, RobloxApiClient, RobloxApiError, RobloxCredentials, UploadResponse,
+};
+
+impl From<AssetType> for RbxAssetType {
+    /// Panics on [`AssetType::Mesh`]; callers must reject it before
+    /// converting, since `rbxcloud`'s `AssetType` has no distinct variant for
+    /// it (see [`OpenCloudClient::upload_image`]).
+    fn from(value: AssetType) -> Self {
+        match value {
+            AssetType::Decal => RbxAssetType::DecalPng,
+            AssetType::Audio => RbxAssetType::AudioMp3,
+            AssetType::Model => RbxAssetType::ModelFbx,
+            AssetType::Mesh => unreachable!("Mesh assets must be rejected before this conversion"),
+        }
+    }
+}
 
 pub struct OpenCloudClient<'a> {
     credentials: RobloxCredentials,
@@ -75,12 +92,21 @@ impl<'a> RobloxApiClient<'a> for OpenCloudClient<'a> {
     // }
 
     async fn upload_image(&self, data: ImageUploadData<'a>) -> Result<UploadResponse> {
+        // `rbxcloud`'s `AssetType` has no distinct variant for mesh parts
+        // (only `ModelFbx`), so rather than silently relabeling a mesh
+        // upload as a model, reject it until that's sorted out.
+        if data.asset_type == AssetType::Mesh {
+            bail!(RobloxApiError::UnsupportedAssetType {
+                asset_type: data.asset_type
+            });
+        }
+
         self.upload_image_inner(data).await
     }
 
-    fn download_image(&self, id: u64) -> Result<Vec<u8>> {
+    async fn download_image(&self, id: u64) -> Result<Vec<u8>> {
         todo!();
-        // LegacyClient::new(self.credentials.clone())?.download_image(id)
+        // LegacyClient::new(self.credentials.clone())?.download_image(id).await
     }
 }
 
@@ -88,7 +114,7 @@ impl<'a> OpenCloudClient<'a> {
     async fn upload_image_inner(&self, data: ImageUploadData<'a>) -> Result<UploadResponse> {
         let asset_info = CreateAssetWithContents {
             asset: AssetCreation {
-                asset_type: AssetType::DecalPng,
+                asset_type: data.asset_type.into(),
                 display_name: data.name.to_string(),
                 description: data.description.to_string(),
                 creation_context: AssetCreationContext {
@@ -99,7 +125,10 @@ impl<'a> OpenCloudClient<'a> {
             contents: &data.image_data,
         };
 
-        let response = self.assets.create_with_contents(&asset_info).await?;
+        let response = retry_transient(&self.credentials, || {
+            self.assets.create_with_contents(&asset_info)
+        })
+        .await?;
 
         let Some(operation_id) = response.path else {
             bail!(RobloxApiError::MissingOperationPath);
@@ -119,14 +148,14 @@ impl<'a> OpenCloudClient<'a> {
         let operation = GetAsset { operation_id };
         let asset_id = async {
             loop {
-                let res = self.assets.get(&operation).await?;
+                let res = retry_transient(&self.credentials, || self.assets.get(&operation)).await?;
                 let Some(response) = res.response else {
                     if retry_count > MAX_RETRIES {
                         return Err(RobloxApiError::AssetGetFailed);
                     }
 
                     retry_count += 1;
-                    std::thread::sleep(INITIAL_SLEEP_DURATION * retry_count.pow(BACKOFF));
+                    tokio::time::sleep(INITIAL_SLEEP_DURATION * retry_count.pow(BACKOFF)).await;
                     continue;
                 };
 
@@ -146,6 +175,51 @@ impl<'a> OpenCloudClient<'a> {
     }
 }
 
+/// Run an Open Cloud request, bounding each attempt by `credentials`'
+/// `request_timeout` (since `rbxcloud` builds its own internal HTTP client,
+/// with no hook to configure connect/request timeouts on it directly) and
+/// retrying on a transient HTTP status (429 or 5xx) with the same capped
+/// exponential backoff as [`LegacyClient`](super::legacy::LegacyClient).
+/// Unlike the legacy client, `rbxcloud` doesn't expose response headers, so
+/// this can't honor `Retry-After` and always falls back to backoff. A
+/// timed-out attempt is not retried, matching how `LegacyClient` treats a
+/// connection timeout as fatal rather than transient.
+async fn retry_transient<F, Fut, T>(
+    credentials: &RobloxCredentials,
+    mut make_request: F,
+) -> std::result::Result<T, RobloxApiError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = std::result::Result<T, RbxCloudError>>,
+{
+    let mut attempt = 0;
+
+    loop {
+        let err = match tokio::time::timeout(credentials.request_timeout, make_request()).await {
+            Ok(Ok(value)) => return Ok(value),
+            Ok(Err(err)) => err,
+            Err(_) => return Err(RobloxApiError::Timeout),
+        };
+
+        let is_retryable = matches!(&err, RbxCloudError::HttpStatusError { code, .. }
+            if StatusCode::from_u16(*code).is_ok_and(is_transient));
+
+        if !is_retryable || attempt + 1 >= credentials.max_retries {
+            return Err(err.into());
+        }
+
+        let delay = backoff_delay(attempt);
+        log::warn!(
+            "Open Cloud API request failed with a transient error on attempt {}, retrying in {:?}...",
+            attempt + 1,
+            delay
+        );
+
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
 impl From<RbxCloudError> for RobloxApiError {
     fn from(value: RbxCloudError) -> Self {
         match value {